@@ -0,0 +1,188 @@
+use std::fmt::Write as _;
+use std::str::FromStr;
+use toml::Value;
+
+use crate::path::Matched;
+use crate::Error;
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Format {
+    Bash,
+    Export,
+    Json,
+    Raw,
+    Toml,
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bash" => Ok(Format::Bash),
+            "export" => Ok(Format::Export),
+            "json" => Ok(Format::Json),
+            "raw" => Ok(Format::Raw),
+            "toml" => Ok(Format::Toml),
+            name => Err(Error::BadFormat { name: name.to_string() }),
+        }
+    }
+}
+
+impl Format {
+    pub(crate) fn render(&self, value: &Matched) -> Result<String, Error> {
+        match self {
+            Format::Bash => Bash.render(value),
+            Format::Export => Export.render(value),
+            Format::Json => Json.render(value),
+            Format::Raw => Raw.render(value),
+            Format::Toml => Toml.render(value),
+        }
+    }
+}
+
+trait Render {
+    fn render(&self, value: &Matched) -> Result<String, Error>;
+}
+
+fn is_atomic(obj: &Value) -> bool {
+    use Value::*;
+    matches!(obj, String(_) | Boolean(_) | Integer(_) | Datetime(_) | Float(_))
+}
+
+fn atom_to_string(obj: &Value) -> String {
+    match obj {
+        Value::String(s) => snailquote::unescape(s).unwrap(),
+        Value::Integer(i) => i.to_string(),
+        Value::Boolean(b) => b.to_string(),
+        Value::Float(fl) => fl.to_string(),
+        Value::Datetime(dt) => dt.to_string(),
+        _ => unreachable!(),
+    }
+}
+
+struct Bash;
+
+impl Render for Bash {
+    fn render(&self, value: &Matched) -> Result<String, Error> {
+        let mut out = String::new();
+        match value {
+            Matched::One(value) => write_bash_value(&mut out, value),
+            Matched::Many(values) => {
+                let mut had_one = false;
+                for value in values {
+                    if !is_atomic(value) { continue; }
+
+                    if had_one {
+                        out.push(' ');
+                    } else {
+                        had_one = true;
+                    }
+
+                    out.push_str(&atom_to_string(value));
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+fn write_bash_value(out: &mut String, value: &Value) {
+    match value {
+        Value::Array(arr) => {
+            let mut had_one = false;
+            for elem in arr.iter() {
+                if !is_atomic(elem) { continue; }
+
+                if had_one {
+                    out.push(' ');
+                } else {
+                    had_one = true;
+                }
+
+                out.push_str(&atom_to_string(elem));
+            }
+        }
+        Value::Table(tbl) => {
+            let mut had_one = false;
+            for (key, value) in tbl.iter() {
+                if !is_atomic(value) { continue; }
+
+                if had_one {
+                    out.push(' ');
+                } else {
+                    had_one = true;
+                }
+
+                let _ = write!(out, r#"[{}]="#, snailquote::unescape(key).unwrap());
+                out.push_str(&atom_to_string(value));
+            }
+        }
+        Value::Integer(i) => {
+            let _ = writeln!(out, "{i}");
+        }
+        x => out.push_str(&atom_to_string(x)),
+    }
+}
+
+struct Export;
+
+impl Render for Export {
+    fn render(&self, value: &Matched) -> Result<String, Error> {
+        let mut out = String::new();
+        match value {
+            Matched::One(Value::Table(tbl)) => {
+                for (key, value) in tbl.iter() {
+                    if !is_atomic(value) { continue; }
+                    let _ = writeln!(out, "export {}={}", key, snailquote::escape(&atom_to_string(value)));
+                }
+            }
+            Matched::One(value) if is_atomic(value) => {
+                let _ = writeln!(out, "export VALUE={}", snailquote::escape(&atom_to_string(value)));
+            }
+            Matched::Many(values) => {
+                for (i, value) in values.iter().enumerate() {
+                    if !is_atomic(value) { continue; }
+                    let _ = writeln!(out, "export VALUE_{i}={}", snailquote::escape(&atom_to_string(value)));
+                }
+            }
+            Matched::One(_) => return Err(Error::NotAtomic),
+        }
+        Ok(out)
+    }
+}
+
+struct Json;
+
+impl Render for Json {
+    fn render(&self, value: &Matched) -> Result<String, Error> {
+        let rendered = match value {
+            Matched::One(value) => serde_json::to_string(value),
+            Matched::Many(values) => serde_json::to_string(values),
+        };
+        rendered.map(|mut s| { s.push('\n'); s }).map_err(|source| Error::Serialize { message: source.to_string() })
+    }
+}
+
+struct Raw;
+
+impl Render for Raw {
+    fn render(&self, value: &Matched) -> Result<String, Error> {
+        match value {
+            Matched::One(value) if is_atomic(value) => Ok(format!("{}\n", atom_to_string(value))),
+            _ => Err(Error::NotAtomic),
+        }
+    }
+}
+
+struct Toml;
+
+impl Render for Toml {
+    fn render(&self, value: &Matched) -> Result<String, Error> {
+        let rendered = match value {
+            Matched::One(value) => toml::to_string(value),
+            Matched::Many(values) => toml::to_string(values),
+        };
+        rendered.map_err(|source| Error::Serialize { message: source.to_string() })
+    }
+}
@@ -0,0 +1,78 @@
+use std::fmt;
+
+#[derive(Debug, Default)]
+pub struct Opts {
+    pub pattern: Option<String>,
+    pub input: Option<String>,
+    pub output: Option<String>,
+    pub interactive: bool,
+    pub set: Option<(String, String)>,
+    pub help: bool,
+    pub version: bool,
+}
+
+#[derive(Debug)]
+pub enum OptError {
+    Unknown(String),
+    MissingArg(String),
+    TooManyArgs(String),
+}
+
+impl fmt::Display for OptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptError::Unknown(flag) => write!(f, "unknown option: {flag}"),
+            OptError::MissingArg(flag) => write!(f, "option {flag} requires an argument"),
+            OptError::TooManyArgs(arg) => write!(f, "unexpected argument: {arg}"),
+        }
+    }
+}
+
+impl std::error::Error for OptError {}
+
+/// Walks argv one token at a time, so `-f file pattern` and
+/// `pattern -f file` both resolve to the same `Opts`. `--` stops option
+/// parsing so everything after it is taken as the positional pattern.
+pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Opts, OptError> {
+    let mut opts = Opts::default();
+    let mut args = args.into_iter();
+    let mut no_more_flags = false;
+
+    while let Some(arg) = args.next() {
+        if no_more_flags || arg == "-" || !arg.starts_with('-') {
+            set_pattern(&mut opts, arg)?;
+            continue;
+        }
+
+        match arg.as_str() {
+            "--" => no_more_flags = true,
+            "-h" | "--help" => opts.help = true,
+            "-V" | "--version" => opts.version = true,
+            "-i" | "--interactive" => opts.interactive = true,
+            "-f" | "--file" => {
+                let file = args.next().ok_or_else(|| OptError::MissingArg(arg.clone()))?;
+                opts.input = Some(file);
+            }
+            "-o" | "--output" => {
+                let fmt = args.next().ok_or_else(|| OptError::MissingArg(arg.clone()))?;
+                opts.output = Some(fmt);
+            }
+            "-s" | "--set" => {
+                let path = args.next().ok_or_else(|| OptError::MissingArg(arg.clone()))?;
+                let value = args.next().ok_or_else(|| OptError::MissingArg(arg.clone()))?;
+                opts.set = Some((path, value));
+            }
+            _ => return Err(OptError::Unknown(arg)),
+        }
+    }
+
+    Ok(opts)
+}
+
+fn set_pattern(opts: &mut Opts, arg: String) -> Result<(), OptError> {
+    if opts.pattern.is_some() {
+        return Err(OptError::TooManyArgs(arg));
+    }
+    opts.pattern = Some(arg);
+    Ok(())
+}
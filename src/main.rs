@@ -15,178 +15,152 @@
  * <https://www.gnu.org/licenses/>                               *
  * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * * */
 
-use core::fmt;
-use std::{env, fmt::Display, fs::File, io::{self, BufReader, Read}, process, str::FromStr};
-use toml::Value;
-
-#[derive(Debug)]
-struct Opts {
-    pattern: String,
-    input: Option<String>,
-}
+mod format;
+mod getopt;
+mod path;
+mod repl;
+mod sysexits;
+
+use std::{env, fs::File, io::{self, BufReader, Read}, process};
+
+use format::Format;
+use getopt::Opts;
+use path::{get_path, set_path, ExportSpec};
 
 #[derive(Debug, thiserror::Error)]
-enum Error {
+pub(crate) enum Error {
     #[error("No such key: {key}")]
     NoSuchKey { key: String },
+    #[error("could not open {path}: {source}")]
+    NoInput { path: String, #[source] source: io::Error },
     #[error("IOError: {source}")]
-    IOError { #[from] source: io::Error },
+    Io { #[from] source: io::Error },
+    #[error("{message}")]
+    BadUsage { message: String },
+    #[error("TOMLError: {source}")]
+    Toml { #[from] source: toml::de::Error },
+    #[error("invalid path expression: {spec}")]
+    BadPath { spec: String },
+    #[error("index {index} out of bounds for array of length {len}")]
+    IndexOutOfBounds { index: i64, len: usize },
+    #[error("cannot apply index [{index}] to a non-array value")]
+    NotAnArray { index: i64 },
+    #[error("unknown output format: {name}")]
+    BadFormat { name: String },
+    #[error("this output format requires a single atomic value")]
+    NotAtomic,
+    #[error("could not serialize output: {message}")]
+    Serialize { message: String },
+    #[error("readline error: {source}")]
+    Readline { #[from] source: rustyline::error::ReadlineError },
+    #[error("cannot assign into non-table value at {key}")]
+    NotATable { key: String },
+    #[error("cannot assign through a wildcard or slice path segment")]
+    NotAssignable,
 }
 
-struct ExportSpec {
-    path: Vec<String>,
+impl Error {
+    fn exit_code(&self) -> i32 {
+        use sysexits::*;
+        match self {
+            Error::BadUsage { .. } => EX_USAGE,
+            Error::BadPath { .. } => EX_USAGE,
+            Error::BadFormat { .. } => EX_USAGE,
+            Error::NoInput { .. } => EX_NOINPUT,
+            Error::Toml { .. } => EX_DATAERR,
+            Error::NotAtomic => EX_DATAERR,
+            Error::Serialize { .. } => EX_DATAERR,
+            Error::NoSuchKey { .. } => EX_UNAVAILABLE,
+            Error::IndexOutOfBounds { .. } => EX_UNAVAILABLE,
+            Error::NotAnArray { .. } => EX_UNAVAILABLE,
+            Error::Io { .. } => EX_IOERR,
+            Error::Readline { .. } => EX_IOERR,
+            Error::NotATable { .. } => EX_UNAVAILABLE,
+            Error::NotAssignable => EX_USAGE,
+        }
+    }
 }
 
-impl FromStr for ExportSpec {
-    type Err = Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let path =  s.split('.').map(|s| s.to_string()).collect();
-        Ok(ExportSpec { path })
-    }
+pub(crate) fn load_document(input: Option<String>) -> Result<toml::Value, Error> {
+    let reader: Box<dyn Read> = match input {
+        Some(path) => Box::new(File::open(&path).map_err(|source| Error::NoInput { path, source })?),
+        None => Box::new(io::stdin()),
+    };
+    let mut input_file = BufReader::new(reader);
+    let mut buf = String::new();
+    input_file.read_to_string(&mut buf)?;
+    Ok(toml::from_str(&buf)?)
 }
 
-fn get_path<'a, S>(mut obj: &'a Value, path: &[S]) -> Result<&'a Value, Error>
-    where S: AsRef<str>
-{
-    for part in path.iter() {
-        obj = obj.get(part.as_ref()).ok_or_else(|| {
-            Error::NoSuchKey {
-                key: path.iter().map(|s| s.as_ref()).collect::<Vec<_>>().join(".")
-            }
-        })?;
-    }
-    Ok(obj)
+pub(crate) fn resolve(doc: &toml::Value, pattern: &str, format: &Format) -> Result<String, Error> {
+    let ExportSpec { path } = pattern.parse()?;
+    let value = get_path(doc, &path)?;
+    format.render(&value)
 }
 
-fn is_atomic(obj: &Value) -> bool {
-    use Value::*;
-    matches!(obj, String(_) | Boolean(_) | Integer(_) | Datetime(_) | Float(_))
+pub(crate) fn set(doc: &mut toml::Value, pattern: &str, raw_value: &str) -> Result<(), Error> {
+    let ExportSpec { path } = pattern.parse()?;
+    let value = path::parse_scalar(raw_value);
+    set_path(doc, &path, value)
 }
 
-fn write_atom(f: &mut fmt::Formatter<'_>, obj: &Value) -> fmt::Result {
-    match obj {
-        Value::String(s) => write!(f, "{}", snailquote::unescape(s).unwrap())?,
-        Value::Integer(i) => write!(f, "{i}")?,
-        Value::Boolean(b) => write!(f, "{b}")?,
-        Value::Float(fl) => write!(f, "{fl}")?,
-        Value::Datetime(dt) => write!(f, "{dt}")?,
-        _ => unreachable!(),
+fn doit(opts: Opts) -> Result<(), Error> {
+    if let Some((path, raw_value)) = opts.set {
+        let mut doc = load_document(opts.input)?;
+        set(&mut doc, &path, &raw_value)?;
+        let rendered = toml::to_string(&doc).map_err(|source| Error::Serialize { message: source.to_string() })?;
+        print!("{rendered}");
+        return Ok(());
     }
-    Ok(())
-}
 
-struct FmtBash<'a> {
-    value: &'a toml::Value,
-}
+    let format: Format = opts.output.as_deref().unwrap_or("bash").parse()?;
+    let doc = load_document(opts.input)?;
 
-impl<'a> Display for FmtBash<'a> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self.value {
-            Value::Array(arr) => {
-                let mut had_one = false;
-                for elem in arr.iter() {
-                    if !is_atomic(elem) { continue; }
-
-                    if had_one {
-                        write!(f, " ")?;
-                    } else {
-                        had_one = true;
-                    }
-
-                    write_atom(f, elem)?;
-                }
-            }
-            Value::Table(tbl) => {
-                let mut had_one = false;
-                for (key, value) in tbl.iter() {
-                    if !is_atomic(value) { continue; }
-
-                    if had_one {
-                        write!(f, " ")?;
-                    } else {
-                        had_one = true;
-                    }
-
-                    write!(f, r#"[{}]="#, snailquote::unescape(key).unwrap())?;
-                    write_atom(f, value)?;
-                }
-            }
-            Value::Integer(i) => writeln!(f, r#"{i}"#)?,
-            x => {
-                write_atom(f, x)?;
-            }
-        }
-        Ok(())
+    if opts.interactive {
+        return repl::run(&doc, &format);
     }
-}
 
-fn doit(opts: Opts) -> Result<(), Box<dyn std::error::Error>> {
-    let mut input_file = BufReader::new(
-        opts.input.map(|f| -> Result<Box<dyn Read>, io::Error> {
-            Ok(Box::new(File::open(f)?))
-        }).unwrap_or_else(|| Ok(Box::new(io::stdin())))?);
-    let mut input = String::new();
-    input_file.read_to_string(&mut input)?;
-    let obj: toml::Value = toml::from_str(&input)?;
-    let ExportSpec { path } = opts.pattern.parse()?;
-    let value = get_path(&obj, &path)?;
-    print!("{}", FmtBash { value });
+    let pattern = opts.pattern.ok_or_else(|| Error::BadUsage {
+        message: "missing pattern argument".to_string(),
+    })?;
+    print!("{}", resolve(&doc, &pattern, &format)?);
     Ok(())
 }
 
 fn main() {
     let name = env!("CARGO_PKG_NAME");
     let version = env!("CARGO_PKG_VERSION");
-    let args: Vec<String> = env::args().collect();
-    match args.len() {
-        1 => {
-            eprintln!("{name} - command line TOML processor [version {version}] \n");
-            eprintln!("{}", include_str!("../docs/header.txt"));
-            eprintln!("{}", include_str!("../docs/opt-help.txt"));
-        }
-        2 => { // read input from stdin
-            let arg = &args[1];
-            if arg == "--help" || arg == "-h" {
-                eprintln!("{name} {version}\n");
-                eprintln!("{}", include_str!("../docs/opt-help.txt"));
-                return;
-            }
-            if arg == "--version" || arg == "-V" {
-                eprintln!("{name} {version}");
-                return;
-            }
-            let input = None;
-            let opts = Opts { pattern: arg.clone(), input };
-            if let Err(e) = doit(opts) {
-                eprintln!("{e}");
-                process::exit(1);
-            }
-        }
-        3 => { // read input from file
-            eprintln!("{}", include_str!("../docs/opt-help.txt"));
-        }
-        4 => { // read input from file
-            {
-                let shorthand = &args[1];
-                if !(shorthand == "--file" || shorthand == "-f") {
-                    eprintln!("{}", include_str!("../docs/opt-help.txt"));
-                    return;
-                }
-            }
-
-            let filename =  &args[2];
-            let pattern =  &args[3];
-
-            let opts = Opts { pattern: pattern.clone(), input: Option::from(filename.clone()) };
-            if let Err(e) = doit(opts) {
-                eprintln!("{e}");
-                process::exit(1);
-            }
-        }
-        _ => {
-            eprintln!("too lot of arguments");
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if args.is_empty() {
+        eprintln!("{name} - command line TOML processor [version {version}] \n");
+        eprintln!("{}", include_str!("../docs/header.txt"));
+        eprintln!("{}", include_str!("../docs/opt-help.txt"));
+        return;
+    }
+
+    let opts = match getopt::parse(args) {
+        Ok(opts) => opts,
+        Err(e) => {
+            eprintln!("{e}");
             eprintln!("{}", include_str!("../docs/opt-help.txt"));
+            process::exit(sysexits::EX_USAGE);
         }
+    };
+
+    if opts.help {
+        eprintln!("{name} {version}\n");
+        eprintln!("{}", include_str!("../docs/opt-help.txt"));
+        return;
+    }
+
+    if opts.version {
+        eprintln!("{name} {version}");
+        return;
+    }
+
+    if let Err(e) = doit(opts) {
+        eprintln!("{e}");
+        process::exit(e.exit_code());
     }
 }
@@ -0,0 +1,201 @@
+use std::str::FromStr;
+use toml::Value;
+
+use crate::Error;
+
+#[derive(Debug, Clone)]
+pub(crate) enum PathSegment {
+    Key(String),
+    Index(i64),
+    Wildcard,
+    Slice(Option<i64>, Option<i64>),
+}
+
+pub(crate) struct ExportSpec {
+    pub path: Vec<PathSegment>,
+}
+
+impl FromStr for ExportSpec {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut path = Vec::new();
+        for chunk in s.split('.') {
+            parse_chunk(chunk, &mut path)?;
+        }
+        Ok(ExportSpec { path })
+    }
+}
+
+/// Splits a single dot-separated chunk into an optional bare key followed
+/// by zero or more bracketed index/wildcard/slice segments, e.g.
+/// `hosts[0][-1]` is a `Key` followed by two `Index` segments.
+fn parse_chunk(chunk: &str, path: &mut Vec<PathSegment>) -> Result<(), Error> {
+    let (key, mut rest) = match chunk.find('[') {
+        Some(i) => (&chunk[..i], &chunk[i..]),
+        None => (chunk, ""),
+    };
+    if !key.is_empty() {
+        path.push(PathSegment::Key(key.to_string()));
+    }
+    while !rest.is_empty() {
+        let close = rest.find(']').ok_or_else(|| Error::BadPath { spec: chunk.to_string() })?;
+        path.push(parse_bracket(&rest[1..close], chunk)?);
+        rest = &rest[close + 1..];
+    }
+    Ok(())
+}
+
+fn parse_bracket(inner: &str, spec: &str) -> Result<PathSegment, Error> {
+    let bad_path = || Error::BadPath { spec: spec.to_string() };
+
+    if inner == "*" {
+        return Ok(PathSegment::Wildcard);
+    }
+    if let Some((start, end)) = inner.split_once(':') {
+        let start = if start.is_empty() { None } else { Some(start.parse().map_err(|_| bad_path())?) };
+        let end = if end.is_empty() { None } else { Some(end.parse().map_err(|_| bad_path())?) };
+        return Ok(PathSegment::Slice(start, end));
+    }
+    Ok(PathSegment::Index(inner.parse().map_err(|_| bad_path())?))
+}
+
+/// The result of resolving an `ExportSpec` path: either the single value a
+/// plain key/index chain landed on, or the collection a wildcard or slice
+/// segment fanned out to.
+pub(crate) enum Matched<'a> {
+    One(&'a Value),
+    Many(Vec<&'a Value>),
+}
+
+pub(crate) fn get_path<'a>(obj: &'a Value, path: &[PathSegment]) -> Result<Matched<'a>, Error> {
+    let mut current: Vec<&Value> = vec![obj];
+    let mut collection = false;
+
+    for segment in path {
+        current = match segment {
+            PathSegment::Key(key) => current
+                .into_iter()
+                .map(|v| v.get(key.as_str()).ok_or_else(|| Error::NoSuchKey { key: key.clone() }))
+                .collect::<Result<_, _>>()?,
+            PathSegment::Index(i) => current.into_iter().map(|v| index_array(v, *i)).collect::<Result<_, _>>()?,
+            PathSegment::Slice(start, end) => {
+                collection = true;
+                current
+                    .into_iter()
+                    .map(|v| slice_array(v, *start, *end))
+                    .collect::<Result<Vec<Vec<&Value>>, _>>()?
+                    .into_iter()
+                    .flatten()
+                    .collect()
+            }
+            PathSegment::Wildcard => {
+                collection = true;
+                current
+                    .into_iter()
+                    .map(wildcard)
+                    .collect::<Result<Vec<Vec<&Value>>, _>>()?
+                    .into_iter()
+                    .flatten()
+                    .collect()
+            }
+        };
+    }
+
+    if !collection && current.len() == 1 {
+        Ok(Matched::One(current[0]))
+    } else {
+        Ok(Matched::Many(current))
+    }
+}
+
+fn index_array(v: &Value, i: i64) -> Result<&Value, Error> {
+    let arr = v.as_array().ok_or(Error::NotAnArray { index: i })?;
+    let len = arr.len();
+    let idx = if i < 0 { i + len as i64 } else { i };
+    if idx < 0 || idx as usize >= len {
+        return Err(Error::IndexOutOfBounds { index: i, len });
+    }
+    Ok(&arr[idx as usize])
+}
+
+fn slice_array(v: &Value, start: Option<i64>, end: Option<i64>) -> Result<Vec<&Value>, Error> {
+    let arr = v.as_array().ok_or(Error::NotAnArray { index: start.or(end).unwrap_or(0) })?;
+    let len = arr.len() as i64;
+    let resolve = |i: i64| if i < 0 { (len + i).max(0) } else { i.min(len) };
+    let start = start.map(resolve).unwrap_or(0);
+    let end = end.map(resolve).unwrap_or(len);
+    if start >= end {
+        return Ok(Vec::new());
+    }
+    Ok(arr[start as usize..end as usize].iter().collect())
+}
+
+fn wildcard(v: &Value) -> Result<Vec<&Value>, Error> {
+    match v {
+        Value::Array(arr) => Ok(arr.iter().collect()),
+        Value::Table(tbl) => Ok(tbl.values().collect()),
+        _ => Err(Error::NotAnArray { index: 0 }),
+    }
+}
+
+/// Walks `path` against `obj`, creating intermediate tables as needed, and
+/// assigns `value` at the leaf, replacing whatever the leaf held. A missing
+/// intermediate `Key` followed by another `Key` is materialized as an empty
+/// table; an intermediate `Key` whose existing value is not a table is an
+/// error, since we'd otherwise silently discard it. `Index` segments target
+/// an existing array element in place and never create array elements.
+pub(crate) fn set_path(obj: &mut Value, path: &[PathSegment], value: Value) -> Result<(), Error> {
+    let (segment, rest) = path.split_first().ok_or(Error::NotAssignable)?;
+
+    match segment {
+        PathSegment::Key(key) => {
+            let tbl = obj.as_table_mut().ok_or_else(|| Error::NotATable { key: key.clone() })?;
+            if rest.is_empty() {
+                tbl.insert(key.clone(), value);
+                return Ok(());
+            }
+            if !tbl.contains_key(key) {
+                match &rest[0] {
+                    PathSegment::Key(_) => {
+                        tbl.insert(key.clone(), Value::Table(Default::default()));
+                    }
+                    _ => return Err(Error::NoSuchKey { key: key.clone() }),
+                }
+            }
+            set_path(tbl.get_mut(key).expect("just checked/inserted"), rest, value)
+        }
+        PathSegment::Index(i) => {
+            let arr = obj.as_array_mut().ok_or(Error::NotAnArray { index: *i })?;
+            let len = arr.len();
+            let idx = if *i < 0 { *i + len as i64 } else { *i };
+            if idx < 0 || idx as usize >= len {
+                return Err(Error::IndexOutOfBounds { index: *i, len });
+            }
+            if rest.is_empty() {
+                arr[idx as usize] = value;
+                return Ok(());
+            }
+            set_path(&mut arr[idx as usize], rest, value)
+        }
+        PathSegment::Wildcard | PathSegment::Slice(_, _) => Err(Error::NotAssignable),
+    }
+}
+
+/// Infers a scalar TOML type from a raw `--set` argument: boolean, integer,
+/// float, and datetime are tried in turn, falling back to a plain string.
+pub(crate) fn parse_scalar(raw: &str) -> Value {
+    if let Ok(b) = raw.parse::<bool>() {
+        return Value::Boolean(b);
+    }
+    if let Ok(i) = raw.parse::<i64>() {
+        return Value::Integer(i);
+    }
+    if let Ok(f) = raw.parse::<f64>() {
+        return Value::Float(f);
+    }
+    if let Ok(dt) = raw.parse::<toml::value::Datetime>() {
+        return Value::Datetime(dt);
+    }
+    Value::String(raw.to_string())
+}
@@ -0,0 +1,40 @@
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+use toml::Value;
+
+use crate::format::Format;
+use crate::{resolve, Error};
+
+/// Interactive exploration of an already-loaded document: each line is read
+/// from an editable prompt (history + arrow-key editing via `rustyline`),
+/// parsed as an `ExportSpec` and resolved against `doc`, then printed with
+/// `format`. The document is parsed once by the caller and stays resident
+/// for the whole session, so repeated queries don't re-read the file.
+/// Empty lines are ignored, a resolution error is printed but keeps the
+/// session alive, and EOF or `quit` exits cleanly.
+pub(crate) fn run(doc: &Value, format: &Format) -> Result<(), Error> {
+    let mut editor = DefaultEditor::new()?;
+
+    loop {
+        match editor.readline("tq> ") {
+            Ok(line) => {
+                let line = line.trim();
+                if line.is_empty() {
+                    continue;
+                }
+                let _ = editor.add_history_entry(line);
+                if line == "quit" {
+                    break;
+                }
+                match resolve(doc, line, format) {
+                    Ok(rendered) => print!("{rendered}"),
+                    Err(e) => eprintln!("{e}"),
+                }
+            }
+            Err(ReadlineError::Eof) | Err(ReadlineError::Interrupted) => break,
+            Err(source) => return Err(Error::Readline { source }),
+        }
+    }
+
+    Ok(())
+}
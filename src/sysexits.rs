@@ -0,0 +1,8 @@
+//! A handful of the exit codes from BSD `sysexits.h`, used so that scripts
+//! wrapping `tq` can distinguish failure modes without scraping stderr.
+
+pub const EX_USAGE: i32 = 64;
+pub const EX_DATAERR: i32 = 65;
+pub const EX_NOINPUT: i32 = 66;
+pub const EX_UNAVAILABLE: i32 = 69;
+pub const EX_IOERR: i32 = 74;